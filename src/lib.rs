@@ -1,19 +1,146 @@
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize};
 use std::{convert::TryFrom, fmt, marker::PhantomData, ops::Deref};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Types that expose a length, so they can back a [`LengthLimitedField`].
+///
+/// The length is whatever the container naturally counts: bytes for
+/// `String`/`&str`/`OsString`, elements for `Vec<T>`.
+pub trait HasLength {
+    fn length(&self) -> usize;
+
+    /// A UTF-8 view of the value, when it has one, so a [`LengthUnit`] can
+    /// count `char`s or graphemes. Binary containers return `None` and fall
+    /// back to their [`length`](HasLength::length).
+    fn as_str(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl HasLength for String {
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl HasLength for &str {
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T> HasLength for Vec<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl HasLength for std::ffi::OsString {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+/// The unit a [`LengthLimitedField`] measures its bounds in.
+///
+/// `Bytes` counts UTF-8 bytes (or container elements), `Chars` counts
+/// `char`s, and `Graphemes` counts Unicode extended grapheme clusters — so
+/// `"é😀"` is two characters, not its six UTF-8 bytes.
+pub trait LengthUnit {
+    /// Name of the unit, reported in error and `expecting` messages.
+    const NAME: &'static str;
+
+    /// Whether a raw input byte length equals this unit's count, so it can be
+    /// used to reject oversized input up front without materializing it. True
+    /// only for [`Bytes`]; `char`/grapheme counts are always `<=` the byte
+    /// length, so a byte length over `MAX` does not imply the count is.
+    const BYTE_EXACT: bool = false;
+
+    fn measure<T: HasLength>(value: &T) -> usize;
+}
 
 #[derive(Debug)]
-pub struct LengthLimitedField<const MIN: usize, const MAX: usize> {
-    pub(crate) inner: String,
+pub struct Bytes;
+
+#[derive(Debug)]
+pub struct Chars;
+
+#[derive(Debug)]
+pub struct Graphemes;
+
+impl LengthUnit for Bytes {
+    const NAME: &'static str = "bytes";
+    const BYTE_EXACT: bool = true;
+
+    fn measure<T: HasLength>(value: &T) -> usize {
+        value.length()
+    }
 }
 
-impl<const MIN: usize, const MAX: usize> LengthLimitedField<MIN, MAX> {
-    pub fn new(value: &str) -> Result<Self, LengthLimitedFieldError> {
-        Self::try_from(value)
+impl LengthUnit for Chars {
+    const NAME: &'static str = "chars";
+
+    fn measure<T: HasLength>(value: &T) -> usize {
+        value
+            .as_str()
+            .map(|s| s.chars().count())
+            .unwrap_or_else(|| value.length())
     }
 }
 
-impl<const MIN: usize, const MAX: usize> Deref for LengthLimitedField<MIN, MAX> {
-    type Target = String;
+impl LengthUnit for Graphemes {
+    const NAME: &'static str = "graphemes";
+
+    fn measure<T: HasLength>(value: &T) -> usize {
+        value
+            .as_str()
+            .map(|s| s.graphemes(true).count())
+            .unwrap_or_else(|| value.length())
+    }
+}
+
+#[derive(Debug)]
+pub struct LengthLimitedField<T, const MIN: usize, const MAX: usize, U = Bytes> {
+    pub(crate) inner: T,
+    marker: PhantomData<fn() -> U>,
+}
+
+impl<T: HasLength, const MIN: usize, const MAX: usize, U: LengthUnit>
+    LengthLimitedField<T, MIN, MAX, U>
+{
+    pub fn new(value: T) -> Result<Self, LengthLimitedFieldError> {
+        Self::checked(value)
+    }
+
+    /// Validate `value`'s length against `MIN`/`MAX` in this field's unit and
+    /// wrap it. The inherent counterpart to `TryFrom`, which cannot be
+    /// implemented here because `T` is generic (it would collide with core's
+    /// reflexive `TryFrom<T> for T`).
+    pub fn checked(value: T) -> Result<Self, LengthLimitedFieldError> {
+        let max = MAX;
+        let min = MIN;
+        let unit = U::NAME;
+        match U::measure(&value) {
+            len if len > MAX => Err(LengthLimitedFieldError::TooLong { len, max, unit }),
+            len if len < MIN => Err(LengthLimitedFieldError::TooShort { len, min, unit }),
+            _ => Ok(LengthLimitedField {
+                inner: value,
+                marker: PhantomData,
+            }),
+        }
+    }
+}
+
+impl<T, const MIN: usize, const MAX: usize, U> Deref for LengthLimitedField<T, MIN, MAX, U> {
+    type Target = T;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
@@ -22,85 +149,483 @@ impl<const MIN: usize, const MAX: usize> Deref for LengthLimitedField<MIN, MAX>
 
 #[derive(Debug, thiserror::Error)]
 pub enum LengthLimitedFieldError {
-    #[error("Length of value {len:?} longer than {max:?}")]
-    TooLong { len: usize, max: usize },
-    #[error("Length of value {len:?} shorter than {min:?}")]
-    TooShort { len: usize, min: usize },
+    #[error("Length of value {len:?} {unit} longer than {max:?}")]
+    TooLong {
+        len: usize,
+        max: usize,
+        unit: &'static str,
+    },
+    #[error("Length of value {len:?} {unit} shorter than {min:?}")]
+    TooShort {
+        len: usize,
+        min: usize,
+        unit: &'static str,
+    },
+}
+
+impl<T: Serialize, const MIN: usize, const MAX: usize, U> Serialize
+    for LengthLimitedField<T, MIN, MAX, U>
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.inner.serialize(serializer)
+    }
+}
+
+/// Reject an input whose raw byte length already exceeds `MAX`, before it is
+/// copied. Only sound when the unit counts bytes exactly ([`LengthUnit::BYTE_EXACT`]);
+/// otherwise the precise check after materialization does the work.
+fn reject_oversized<U: LengthUnit, E>(len: usize, max: usize) -> Result<(), E>
+where
+    E: serde::de::Error,
+{
+    if U::BYTE_EXACT && len > max {
+        return Err(serde::de::Error::custom(format!(
+            "{}",
+            LengthLimitedFieldError::TooLong {
+                len,
+                max,
+                unit: U::NAME,
+            }
+        )));
+    }
+    Ok(())
+}
+
+struct LengthLimitedStringVisitor<const MIN: usize, const MAX: usize, U> {
+    marker: PhantomData<fn() -> U>,
+}
+
+impl<const MIN: usize, const MAX: usize, U> LengthLimitedStringVisitor<MIN, MAX, U> {
+    fn new() -> Self {
+        LengthLimitedStringVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, const MIN: usize, const MAX: usize, U: LengthUnit> Visitor<'de>
+    for LengthLimitedStringVisitor<MIN, MAX, U>
+{
+    type Value = LengthLimitedField<String, MIN, MAX, U>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_fmt(format_args!(
+            "a string between {} and {} {}",
+            MIN,
+            MAX,
+            U::NAME
+        ))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        // Bound the allocation before copying the borrowed string.
+        reject_oversized::<U, E>(v.len(), MAX)?;
+        LengthLimitedField::checked(v.to_string())
+            .map_err(|error| serde::de::Error::custom(format!("{}", error)))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_str(v)
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        reject_oversized::<U, E>(v.len(), MAX)?;
+        LengthLimitedField::checked(v)
+            .map_err(|error| serde::de::Error::custom(format!("{}", error)))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        reject_oversized::<U, E>(v.len(), MAX)?;
+        let value = std::str::from_utf8(v)
+            .map_err(serde::de::Error::custom)?
+            .to_string();
+        LengthLimitedField::checked(value)
+            .map_err(|error| serde::de::Error::custom(format!("{}", error)))
+    }
+}
+
+impl<'de, const MIN: usize, const MAX: usize, U: LengthUnit> Deserialize<'de>
+    for LengthLimitedField<String, MIN, MAX, U>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(LengthLimitedStringVisitor::new())
+    }
+}
+
+struct LengthLimitedBytesVisitor<const MIN: usize, const MAX: usize, U> {
+    marker: PhantomData<fn() -> U>,
+}
+
+impl<const MIN: usize, const MAX: usize, U> LengthLimitedBytesVisitor<MIN, MAX, U> {
+    fn new() -> Self {
+        LengthLimitedBytesVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, const MIN: usize, const MAX: usize, U: LengthUnit> Visitor<'de>
+    for LengthLimitedBytesVisitor<MIN, MAX, U>
+{
+    type Value = LengthLimitedField<Vec<u8>, MIN, MAX, U>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_fmt(format_args!(
+            "a byte array between {} and {} {}",
+            MIN,
+            MAX,
+            U::NAME
+        ))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        // Bound the allocation before copying the borrowed bytes.
+        reject_oversized::<U, E>(v.len(), MAX)?;
+        LengthLimitedField::checked(v.to_vec())
+            .map_err(|error| serde::de::Error::custom(format!("{}", error)))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        reject_oversized::<U, E>(v.len(), MAX)?;
+        LengthLimitedField::checked(v)
+            .map_err(|error| serde::de::Error::custom(format!("{}", error)))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut value = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(MAX));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            // Refuse to grow the buffer past MAX for a byte-counted field.
+            if U::BYTE_EXACT && value.len() >= MAX {
+                return Err(serde::de::Error::custom(format!(
+                    "{}",
+                    LengthLimitedFieldError::TooLong {
+                        len: value.len() + 1,
+                        max: MAX,
+                        unit: U::NAME,
+                    }
+                )));
+            }
+            value.push(byte);
+        }
+        LengthLimitedField::checked(value)
+            .map_err(|error| serde::de::Error::custom(format!("{}", error)))
+    }
+}
+
+impl<'de, const MIN: usize, const MAX: usize, U: LengthUnit> Deserialize<'de>
+    for LengthLimitedField<Vec<u8>, MIN, MAX, U>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(LengthLimitedBytesVisitor::new())
+    }
+}
+
+#[derive(Debug)]
+pub struct RangeLimitedField<const MIN: i64, const MAX: i64> {
+    pub(crate) inner: i64,
+}
+
+impl<const MIN: i64, const MAX: i64> RangeLimitedField<MIN, MAX> {
+    pub fn new(value: i64) -> Result<Self, RangeLimitedFieldError> {
+        Self::try_from(value)
+    }
 }
 
-impl<const MIN: usize, const MAX: usize> TryFrom<&str> for LengthLimitedField<MIN, MAX> {
-    type Error = LengthLimitedFieldError;
+impl<const MIN: i64, const MAX: i64> Deref for RangeLimitedField<MIN, MAX> {
+    type Target = i64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
+#[derive(Debug, thiserror::Error)]
+pub enum RangeLimitedFieldError {
+    #[error("Value {value:?} greater than {max:?}")]
+    TooHigh { value: i64, max: i64 },
+    #[error("Value {value:?} less than {min:?}")]
+    TooLow { value: i64, min: i64 },
+}
+
+impl<const MIN: i64, const MAX: i64> TryFrom<i64> for RangeLimitedField<MIN, MAX> {
+    type Error = RangeLimitedFieldError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
         let max = MAX;
         let min = MIN;
-        match value.len() {
-            len if len > MAX => Err(LengthLimitedFieldError::TooLong { len, max }),
-            len if len < MIN => Err(LengthLimitedFieldError::TooShort { len, min }),
-            _ => Ok(LengthLimitedField {
-                inner: value.to_string(),
-            }),
+        match value {
+            value if value > MAX => Err(RangeLimitedFieldError::TooHigh { value, max }),
+            value if value < MIN => Err(RangeLimitedFieldError::TooLow { value, min }),
+            _ => Ok(RangeLimitedField { inner: value }),
         }
     }
 }
 
-impl<const MIN: usize, const MAX: usize> Serialize for LengthLimitedField<MIN, MAX> {
+impl<const MIN: i64, const MAX: i64> Serialize for RangeLimitedField<MIN, MAX> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.inner)
+        serializer.serialize_i64(self.inner)
     }
 }
 
-struct LengthLimitedFieldVisitor<const MIN: usize, const MAX: usize> {
-    marker: PhantomData<fn() -> LengthLimitedField<MIN, MAX>>,
+struct RangeLimitedFieldVisitor<const MIN: i64, const MAX: i64> {
+    marker: PhantomData<fn() -> RangeLimitedField<MIN, MAX>>,
 }
 
-impl<const MIN: usize, const MAX: usize> LengthLimitedFieldVisitor<MIN, MAX> {
+impl<const MIN: i64, const MAX: i64> RangeLimitedFieldVisitor<MIN, MAX> {
     fn new() -> Self {
-        LengthLimitedFieldVisitor {
+        RangeLimitedFieldVisitor {
             marker: PhantomData,
         }
     }
 }
 
-impl<'de, const MIN: usize, const MAX: usize> Visitor<'de> for LengthLimitedFieldVisitor<MIN, MAX> {
+impl<'de, const MIN: i64, const MAX: i64> Visitor<'de> for RangeLimitedFieldVisitor<MIN, MAX> {
     // The type that our Visitor is going to produce.
-    type Value = LengthLimitedField<MIN, MAX>;
+    type Value = RangeLimitedField<MIN, MAX>;
 
     // Format a message stating what data this Visitor expects to receive.
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_fmt(format_args!(
-            "a string with length less than {} and greater than {}",
+            "an integer greater than or equal to {} and less than or equal to {}",
             MIN, MAX
         ))
     }
 
-    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        RangeLimitedField::try_from(v)
+            .map_err(|error| serde::de::Error::custom(format!("{}", error)))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
-        LengthLimitedField::try_from(v)
+        // A u64 past i64::MAX is necessarily above any i64 bound; report the
+        // offending value rather than the bound.
+        let v = i64::try_from(v).map_err(|_| {
+            serde::de::Error::custom(format!("Value {} greater than {}", v, MAX))
+        })?;
+        RangeLimitedField::try_from(v)
             .map_err(|error| serde::de::Error::custom(format!("{}", error)))
     }
 }
 
-impl<'de, const MIN: usize, const MAX: usize> Deserialize<'de> for LengthLimitedField<MIN, MAX> {
+impl<'de, const MIN: i64, const MAX: i64> Deserialize<'de> for RangeLimitedField<MIN, MAX> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let field = deserializer.deserialize_string(LengthLimitedFieldVisitor::new())?;
+        let field = deserializer.deserialize_i64(RangeLimitedFieldVisitor::new())?;
         Ok(field)
     }
 }
 
+/// Errors decoding a ShortU16 length prefix or the body it frames.
+#[derive(Debug, thiserror::Error)]
+pub enum ShortU16Error {
+    #[error("third length byte still has its continuation bit set")]
+    ByteThreeContinues,
+    #[error("length was not encoded in the fewest possible bytes")]
+    Alias,
+    #[error("decoded length overflows a u16")]
+    Overflow,
+    #[error("ran out of bytes while decoding the length prefix or its body")]
+    Truncated,
+}
+
+/// Append `value` to `out` using Solana's ShortU16 varint scheme: the low
+/// seven bits of each byte carry data, the high bit signals another byte
+/// follows. At most three bytes are emitted.
+pub(crate) fn encode_shortu16(value: u16, out: &mut Vec<u8>) {
+    let mut rem = value;
+    loop {
+        let mut byte = (rem & 0x7f) as u8;
+        rem >>= 7;
+        if rem == 0 {
+            out.push(byte);
+            break;
+        }
+        byte |= 0x80;
+        out.push(byte);
+    }
+}
+
+/// Decode a ShortU16 length prefix, returning the value and the number of
+/// bytes it consumed. Rejects non-minimal encodings ([`ShortU16Error::Alias`]),
+/// an over-long prefix ([`ShortU16Error::ByteThreeContinues`]), and values that
+/// do not fit a `u16` ([`ShortU16Error::Overflow`]).
+pub(crate) fn decode_shortu16(bytes: &[u8]) -> Result<(u16, usize), ShortU16Error> {
+    let mut value: u32 = 0;
+    for i in 0..=2 {
+        let byte = *bytes.get(i).ok_or(ShortU16Error::Truncated)?;
+        value |= ((byte & 0x7f) as u32) << (7 * i);
+        let more = (byte & 0x80) != 0;
+        if i == 2 && more {
+            return Err(ShortU16Error::ByteThreeContinues);
+        }
+        if !more {
+            // A trailing zero byte beyond the first means a shorter encoding existed.
+            if i > 0 && byte == 0 {
+                return Err(ShortU16Error::Alias);
+            }
+            if value > u16::MAX as u32 {
+                return Err(ShortU16Error::Overflow);
+            }
+            return Ok((value as u16, i + 1));
+        }
+    }
+    // Unreachable: the i == 2 arm always returns.
+    Err(ShortU16Error::ByteThreeContinues)
+}
+
+/// Opt-in wrapper that serializes a [`LengthLimitedField<String, MIN, MAX>`]
+/// as a space-efficient ShortU16 length prefix followed by the raw UTF-8
+/// bytes, instead of as a plain serde string. Useful for binary formats.
+#[derive(Debug)]
+pub struct ShortU16Prefixed<const MIN: usize, const MAX: usize> {
+    pub(crate) inner: LengthLimitedField<String, MIN, MAX>,
+}
+
+impl<const MIN: usize, const MAX: usize> ShortU16Prefixed<MIN, MAX> {
+    pub fn new(value: String) -> Result<Self, LengthLimitedFieldError> {
+        Ok(ShortU16Prefixed {
+            inner: LengthLimitedField::checked(value)?,
+        })
+    }
+}
+
+impl<const MIN: usize, const MAX: usize> Deref for ShortU16Prefixed<MIN, MAX> {
+    type Target = LengthLimitedField<String, MIN, MAX>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<const MIN: usize, const MAX: usize> Serialize for ShortU16Prefixed<MIN, MAX> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let bytes = self.inner.as_bytes();
+        let mut buf = Vec::with_capacity(3 + bytes.len());
+        encode_shortu16(bytes.len() as u16, &mut buf);
+        buf.extend_from_slice(bytes);
+        serializer.serialize_bytes(&buf)
+    }
+}
+
+struct ShortU16PrefixedVisitor<const MIN: usize, const MAX: usize> {
+    marker: PhantomData<fn() -> ShortU16Prefixed<MIN, MAX>>,
+}
+
+impl<const MIN: usize, const MAX: usize> ShortU16PrefixedVisitor<MIN, MAX> {
+    fn new() -> Self {
+        ShortU16PrefixedVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, const MIN: usize, const MAX: usize> Visitor<'de> for ShortU16PrefixedVisitor<MIN, MAX> {
+    type Value = ShortU16Prefixed<MIN, MAX>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_fmt(format_args!(
+            "a ShortU16 length prefix followed by between {} and {} bytes",
+            MIN, MAX
+        ))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let (len, consumed) =
+            decode_shortu16(v).map_err(|error| serde::de::Error::custom(format!("{}", error)))?;
+        let len = len as usize;
+        // Enforce the bounds on the declared length before reading the body.
+        if len > MAX {
+            return Err(serde::de::Error::custom(format!(
+                "{}",
+                LengthLimitedFieldError::TooLong {
+                    len,
+                    max: MAX,
+                    unit: Bytes::NAME,
+                }
+            )));
+        }
+        if len < MIN {
+            return Err(serde::de::Error::custom(format!(
+                "{}",
+                LengthLimitedFieldError::TooShort {
+                    len,
+                    min: MIN,
+                    unit: Bytes::NAME,
+                }
+            )));
+        }
+        let body = v
+            .get(consumed..consumed + len)
+            .ok_or_else(|| serde::de::Error::custom(format!("{}", ShortU16Error::Truncated)))?;
+        let value = std::str::from_utf8(body)
+            .map_err(serde::de::Error::custom)?
+            .to_string();
+        ShortU16Prefixed::new(value)
+            .map_err(|error| serde::de::Error::custom(format!("{}", error)))
+    }
+}
+
+impl<'de, const MIN: usize, const MAX: usize> Deserialize<'de> for ShortU16Prefixed<MIN, MAX> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(ShortU16PrefixedVisitor::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    type NameField = LengthLimitedField<10, 100>;
+    type NameField = LengthLimitedField<String, 10, 100>;
 
     #[derive(Debug, Serialize, Deserialize)]
     struct MyModel {
@@ -110,13 +635,13 @@ mod tests {
     #[test]
     fn it_serializes() {
         let json = "{\"name\": \"morethantencharacters\"}";
-        let serialized: MyModel = serde_json::from_str(&json).expect("should serialize");
+        let serialized: MyModel = serde_json::from_str(json).expect("should serialize");
         assert_eq!(*serialized.name, "morethantencharacters");
     }
 
     #[test]
     fn it_deserializes() {
-        let name = "morethantencharacters";
+        let name = "morethantencharacters".to_string();
         let name: NameField = NameField::new(name).expect("should construct it");
         let deserialized: String = serde_json::to_string(&MyModel { name }).expect("should deserialize");
         let json = "{\"name\":\"morethantencharacters\"}";
@@ -125,8 +650,8 @@ mod tests {
 
     #[test]
     fn it_errors_too_short() {
-        let name = "small";
-        let res: Result<LengthLimitedField<6, 100>, _> = LengthLimitedField::new(name);
+        let name = "small".to_string();
+        let res: Result<LengthLimitedField<String, 6, 100>, _> = LengthLimitedField::new(name);
         match res {
             Ok(_) => panic!("shouldn't work"),
             Err(LengthLimitedFieldError::TooLong{..}) => panic!("Wrong error"),
@@ -136,12 +661,183 @@ mod tests {
 
     #[test]
     fn it_errors_too_long() {
-        let name = "small";
-        let res: Result<LengthLimitedField<1, 4>, _> = LengthLimitedField::new(name);
+        let name = "small".to_string();
+        let res: Result<LengthLimitedField<String, 1, 4>, _> = LengthLimitedField::new(name);
         match res {
             Ok(_) => panic!("shouldn't work"),
             Err(LengthLimitedFieldError::TooShort{..}) => panic!("Wrong error"),
             _ => {}
         };
     }
+
+    #[test]
+    fn it_limits_byte_vectors() {
+        type TokenName = LengthLimitedField<Vec<u8>, 0, 32>;
+        let ok: Result<TokenName, _> = LengthLimitedField::new(vec![0u8; 32]);
+        assert!(ok.is_ok());
+        let err: Result<TokenName, _> = LengthLimitedField::new(vec![0u8; 33]);
+        match err {
+            Ok(_) => panic!("shouldn't work"),
+            Err(LengthLimitedFieldError::TooShort { .. }) => panic!("Wrong error"),
+            _ => {}
+        };
+    }
+
+    #[test]
+    fn it_counts_chars_not_bytes() {
+        // "é😀" is 2 chars but 6 UTF-8 bytes; byte mode rejects it, char mode accepts.
+        let value = "é😀".to_string();
+        let bytes: Result<LengthLimitedField<String, 1, 4>, _> =
+            LengthLimitedField::new(value.clone());
+        assert!(bytes.is_err());
+        let chars: Result<LengthLimitedField<String, 1, 4, Chars>, _> =
+            LengthLimitedField::new(value.clone());
+        assert!(chars.is_ok());
+        let graphemes: Result<LengthLimitedField<String, 1, 4, Graphemes>, _> =
+            LengthLimitedField::new(value);
+        assert!(graphemes.is_ok());
+    }
+
+    type Port = RangeLimitedField<1, 65535>;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Listener {
+        port: Port,
+    }
+
+    #[test]
+    fn it_rejects_oversized_input_early() {
+        // The declared length exceeds MAX, so deserialization fails with TooLong.
+        let json = "{\"name\": \"waytoolongforthisfieldwhichonlyallowsafewcharacters\"}";
+        type Short = LengthLimitedField<String, 1, 8>;
+        #[derive(Debug, Deserialize)]
+        struct Model {
+            #[allow(dead_code)]
+            name: Short,
+        }
+        let err = serde_json::from_str::<Model>(json).expect_err("should reject");
+        assert!(err.to_string().contains("longer than"));
+    }
+
+    #[test]
+    fn shortu16_roundtrips() {
+        for value in [0u16, 1, 127, 128, 16383, 16384, 65535] {
+            let mut buf = Vec::new();
+            encode_shortu16(value, &mut buf);
+            assert!(buf.len() <= 3);
+            let (decoded, consumed) = decode_shortu16(&buf).expect("should decode");
+            assert_eq!(decoded, value);
+            assert_eq!(consumed, buf.len());
+        }
+    }
+
+    #[test]
+    fn shortu16_rejects_aliased_encoding() {
+        match decode_shortu16(&[0x80, 0x00]) {
+            Err(ShortU16Error::Alias) => {}
+            other => panic!("expected Alias, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn shortu16_rejects_overlong_prefix() {
+        match decode_shortu16(&[0xff, 0xff, 0xff]) {
+            Err(ShortU16Error::ByteThreeContinues) => {}
+            other => panic!("expected ByteThreeContinues, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn shortu16_prefixed_validates_on_construction() {
+        type Name = ShortU16Prefixed<1, 16>;
+        assert!(Name::new("hello".to_string()).is_ok());
+        let too_long = Name::new("this name is far too long".to_string());
+        match too_long {
+            Ok(_) => panic!("shouldn't work"),
+            Err(LengthLimitedFieldError::TooShort { .. }) => panic!("Wrong error"),
+            _ => {}
+        };
+    }
+
+    #[test]
+    fn shortu16_prefixed_binary_roundtrip() {
+        type Name = ShortU16Prefixed<1, 16>;
+        let name = Name::new("hello".to_string()).expect("should construct");
+
+        // The wire form is a ShortU16 length prefix followed by the raw bytes.
+        let bytes = bincode::serialize(&name).expect("should serialize");
+        let mut expected = Vec::new();
+        encode_shortu16(b"hello".len() as u16, &mut expected);
+        expected.extend_from_slice(b"hello");
+        // bincode frames serialize_bytes with an 8-byte little-endian length.
+        assert_eq!(&bytes[8..], expected.as_slice());
+
+        let decoded: Name = bincode::deserialize(&bytes).expect("should deserialize");
+        assert_eq!(decoded.as_str(), "hello");
+    }
+
+    #[test]
+    fn shortu16_prefixed_rejects_bad_declared_length() {
+        use serde::de::value::{BytesDeserializer, Error as ValueError};
+        type Name = ShortU16Prefixed<2, 8>;
+
+        // A declared length above MAX is rejected before the body is read.
+        let mut wire = Vec::new();
+        encode_shortu16(50, &mut wire);
+        wire.extend_from_slice(&[b'x'; 50]);
+        let de = BytesDeserializer::<ValueError>::new(&wire);
+        let err = Name::deserialize(de).expect_err("should reject over-max");
+        assert!(err.to_string().contains("longer than"));
+
+        // A declared length below MIN is rejected too.
+        let mut wire = Vec::new();
+        encode_shortu16(1, &mut wire);
+        wire.extend_from_slice(b"x");
+        let de = BytesDeserializer::<ValueError>::new(&wire);
+        let err = Name::deserialize(de).expect_err("should reject under-min");
+        assert!(err.to_string().contains("shorter than"));
+
+        // A prefix claiming more bytes than are present is rejected.
+        let mut wire = Vec::new();
+        encode_shortu16(5, &mut wire);
+        wire.extend_from_slice(b"ab");
+        let de = BytesDeserializer::<ValueError>::new(&wire);
+        let err = Name::deserialize(de).expect_err("should reject truncated body");
+        assert!(err.to_string().contains("ran out of bytes"));
+    }
+
+    #[test]
+    fn range_deserializes() {
+        let json = "{\"port\": 8080}";
+        let serialized: Listener = serde_json::from_str(json).expect("should deserialize");
+        assert_eq!(*serialized.port, 8080);
+    }
+
+    #[test]
+    fn range_serializes() {
+        let port: Port = Port::new(8080).expect("should construct it");
+        let deserialized: String = serde_json::to_string(&Listener { port }).expect("should serialize");
+        let json = "{\"port\":8080}";
+        assert_eq!(deserialized, json);
+    }
+
+    #[test]
+    fn range_errors_too_low() {
+        let res: Result<RangeLimitedField<1, 65535>, _> = RangeLimitedField::new(0);
+        match res {
+            Ok(_) => panic!("shouldn't work"),
+            Err(RangeLimitedFieldError::TooHigh{..}) => panic!("Wrong error"),
+            _ => {}
+        };
+    }
+
+    #[test]
+    fn range_errors_too_high() {
+        let res: Result<RangeLimitedField<1, 4>, _> = RangeLimitedField::new(5);
+        match res {
+            Ok(_) => panic!("shouldn't work"),
+            Err(RangeLimitedFieldError::TooLow{..}) => panic!("Wrong error"),
+            _ => {}
+        };
+    }
 }